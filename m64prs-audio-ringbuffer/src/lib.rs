@@ -0,0 +1,258 @@
+//! A mupen64plus audio plugin that, instead of opening an SDL device, pushes
+//! the game's PCM (converted to `f32`, not resampled) into a lock-free ring
+//! buffer the host application can pull from via [`AudioSink`].
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr},
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        OnceLock,
+    },
+};
+
+mod ring_buffer;
+
+use ring_buffer::RingBuffer;
+
+/// How many interleaved stereo frames the ring buffer holds before the
+/// producer (the real-time audio callback) starts dropping the oldest ones.
+const CAPACITY_FRAMES: usize = 1 << 13; // ~170ms at 48kHz
+const CAPACITY_SAMPLES: usize = CAPACITY_FRAMES * 2;
+
+struct SharedState {
+    ring: RingBuffer,
+    sample_rate: AtomicU32,
+}
+
+static SHARED: OnceLock<SharedState> = OnceLock::new();
+
+fn shared() -> &'static SharedState {
+    SHARED.get_or_init(|| SharedState {
+        ring: RingBuffer::new(CAPACITY_SAMPLES),
+        sample_rate: AtomicU32::new(44100),
+    })
+}
+
+/// Handle for pulling captured PCM out of a running instance of this plugin.
+///
+/// The core loads this plugin via `dlopen`, so [`AudioSink::open`] opens the
+/// *same path a second time*: `dlopen` refcounts by path and hands back the
+/// already-mapped instance rather than a fresh, state-isolated copy, which
+/// is what lets this handle see the samples the core's copy is producing.
+pub struct AudioSink {
+    // Kept alive only to hold the `dlopen` refcount open; never read from.
+    _library: libloading::Library,
+    pull_samples: unsafe extern "C" fn(*mut f32, usize) -> usize,
+    sample_rate: unsafe extern "C" fn() -> u32,
+}
+
+impl AudioSink {
+    /// Opens the ring-buffer plugin at `path` as an audio sink. `path` should
+    /// be the same path passed to `Plugin::load` when attaching it as the
+    /// core's audio plugin.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, libloading::Error> {
+        // SAFETY: the library is mupen64plus-plugin-ABI-compatible, and its
+        // extra `m64prs_audio_ringbuffer_*` exports take no arguments whose
+        // validity depends on initialization order.
+        unsafe {
+            let library = libloading::Library::new(path.as_ref())?;
+            let pull_samples = *library
+                .get::<unsafe extern "C" fn(*mut f32, usize) -> usize>(
+                    b"m64prs_audio_ringbuffer_pull_samples\0",
+                )?;
+            let sample_rate = *library
+                .get::<unsafe extern "C" fn() -> u32>(b"m64prs_audio_ringbuffer_sample_rate\0")?;
+            Ok(Self {
+                _library: library,
+                pull_samples,
+                sample_rate,
+            })
+        }
+    }
+
+    /// Pulls up to `out.len()` interleaved samples, returning how many were
+    /// written; the rest of `out` is left untouched.
+    pub fn pull_samples(&self, out: &mut [f32]) -> usize {
+        // SAFETY: `out` is a valid `&mut [f32]` for its own length, which is
+        // exactly what `m64prs_audio_ringbuffer_pull_samples` expects.
+        unsafe { (self.pull_samples)(out.as_mut_ptr(), out.len()) }
+    }
+
+    /// The sample rate of the captured audio. Set on each DAC rate change to
+    /// a fixed rate for the console's video timing (NTSC/PAL/MPAL); the DAC
+    /// rate the game programs is not read, and the samples are not resampled.
+    pub fn sample_rate(&self) -> u32 {
+        // SAFETY: takes no arguments; always safe to call.
+        unsafe { (self.sample_rate)() }
+    }
+}
+
+/// Pulls up to `len` interleaved samples into `out`, returning how many were
+/// written. Exported so an [`AudioSink`], which reaches this same loaded
+/// instance of the shared library through a second `dlopen` handle, can get
+/// at the ring buffer without depending on Rust-level ABI stability across
+/// the `dlopen` boundary.
+///
+/// # Safety
+/// `out` must be valid for `len` writes of `f32`.
+#[no_mangle]
+pub unsafe extern "C" fn m64prs_audio_ringbuffer_pull_samples(out: *mut f32, len: usize) -> usize {
+    if out.is_null() {
+        return 0;
+    }
+    let out = unsafe { std::slice::from_raw_parts_mut(out, len) };
+    shared().ring.pull(out)
+}
+
+/// See [`m64prs_audio_ringbuffer_pull_samples`].
+#[no_mangle]
+pub extern "C" fn m64prs_audio_ringbuffer_sample_rate() -> u32 {
+    shared().sample_rate.load(Ordering::Relaxed)
+}
+
+// Mupen64plus audio plugin ABI. Only the calls on the audio data path do
+// real work; the rest are lifecycle no-ops, since this plugin has no device
+// of its own to open or close.
+//
+// SAFETY (for every `extern "C" fn` below): these are called exclusively by
+// the mupen64plus core per the plugin spec, with the argument shapes it
+// documents.
+
+#[no_mangle]
+pub extern "C" fn PluginGetVersion(
+    plugin_type: *mut c_int,
+    plugin_version: *mut c_int,
+    api_version: *mut c_int,
+    plugin_name: *mut *const c_char,
+    capabilities: *mut c_int,
+) -> c_int {
+    const NAME: &CStr = c"m64prs ring-buffer audio";
+    unsafe {
+        if !plugin_type.is_null() {
+            *plugin_type = 3; // M64PLUGIN_AUDIO
+        }
+        if !plugin_version.is_null() {
+            *plugin_version = 0x000100;
+        }
+        if !api_version.is_null() {
+            *api_version = 0x020100;
+        }
+        if !plugin_name.is_null() {
+            *plugin_name = NAME.as_ptr();
+        }
+        if !capabilities.is_null() {
+            *capabilities = 0;
+        }
+    }
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn PluginStartup(
+    _core_handle: *mut c_void,
+    _context: *mut c_void,
+    _debug_callback: Option<extern "C" fn(*mut c_void, c_int, *const c_char)>,
+) -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn PluginShutdown() -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn InitiateAudio(_audio_info: *mut c_void) -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn RomOpen() -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn RomClosed() -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+/// Called whenever the game reconfigures the audio interface's DAC rate.
+#[no_mangle]
+pub extern "C" fn AiDacrateChanged(system_type: c_int) {
+    // Matches the fixed DAC-rate table mupen64plus's other audio plugins use
+    // for each console video timing (NTSC/PAL/MPAL).
+    let rate = match system_type {
+        0 => 48_681_812 / 1124, // NTSC
+        1 => 49_656_530 / 1144, // PAL
+        2 => 48_628_316 / 1124, // MPAL
+        _ => 44_100,
+    };
+    shared().sample_rate.store(rate, Ordering::Relaxed);
+}
+
+/// Called once per audio buffer the game hands to the AI; `buffer`/`length`
+/// describe interleaved, big-endian 16-bit stereo PCM in RDRAM, already
+/// copied out by the core into a host-addressable scratch buffer.
+///
+/// # Safety
+/// `buffer` must point at `length` readable bytes, per the plugin ABI.
+#[no_mangle]
+pub unsafe extern "C" fn AiLenChanged(buffer: *const u8, length: c_int) {
+    if buffer.is_null() || length <= 0 {
+        return;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(buffer, length as usize) };
+
+    // Never block the real-time audio callback: convert in fixed-size
+    // chunks on the stack and push straight into the lock-free ring (which
+    // drops the oldest frames on overrun) instead of heap-allocating a
+    // scratch buffer on every call.
+    const CHUNK_SAMPLES: usize = 256;
+    let mut scratch = [0f32; CHUNK_SAMPLES];
+    let mut filled = 0;
+    for chunk in bytes.chunks_exact(2) {
+        let sample_i16 = i16::from_be_bytes([chunk[0], chunk[1]]);
+        scratch[filled] = sample_i16 as f32 / i16::MAX as f32;
+        filled += 1;
+        if filled == CHUNK_SAMPLES {
+            shared().ring.push(&scratch);
+            filled = 0;
+        }
+    }
+    if filled > 0 {
+        shared().ring.push(&scratch[..filled]);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn SetSpeedFactor(_percent: c_int) -> c_int {
+    0 // M64ERR_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C" fn VolumeIsAvailable() -> c_int {
+    0 // false: no hardware volume control to expose
+}
+
+#[no_mangle]
+pub extern "C" fn VolumeUp() {}
+
+#[no_mangle]
+pub extern "C" fn VolumeDown() {}
+
+#[no_mangle]
+pub extern "C" fn VolumeGetLevel() -> c_int {
+    100
+}
+
+#[no_mangle]
+pub extern "C" fn VolumeSetLevel(_level: c_int) {}
+
+#[no_mangle]
+pub extern "C" fn VolumeMute() {}
+
+#[no_mangle]
+pub extern "C" fn VolumeGetString() -> *const c_char {
+    c"n/a".as_ptr()
+}
@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+/// Lock-free single-producer/single-consumer ring buffer of interleaved
+/// `f32` PCM samples, sized to a power of two so indices wrap with a mask
+/// instead of a division.
+///
+/// The producer (the real-time audio callback) must never block, so on
+/// overrun it drops the oldest queued samples by shoving `read` forward
+/// instead of waiting for the consumer to catch up.
+pub(crate) struct RingBuffer {
+    data: Box<[AtomicU32]>,
+    mask: usize,
+    write: AtomicUsize,
+    read: AtomicUsize,
+}
+
+impl RingBuffer {
+    pub(crate) fn new(capacity_samples: usize) -> Self {
+        let capacity_samples = capacity_samples.next_power_of_two();
+        Self {
+            data: (0..capacity_samples).map(|_| AtomicU32::new(0)).collect(),
+            mask: capacity_samples - 1,
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    /// Pushes `samples`, dropping the oldest queued samples if they would
+    /// overflow the buffer. Only the producer may call this.
+    ///
+    /// `read` is otherwise owned by the consumer (see [`Self::pull`]), so an
+    /// overrun here only ever nudges it *forward* via CAS, retrying against
+    /// whatever the consumer most recently advanced it to. A plain `store`
+    /// could stomp a concurrent `pull` and rewind `read` past samples
+    /// already handed out, serving them a second time.
+    pub(crate) fn push(&self, samples: &[f32]) {
+        let mut write = self.write.load(Ordering::Relaxed);
+        for &sample in samples {
+            self.data[write & self.mask].store(sample.to_bits(), Ordering::Relaxed);
+            write = write.wrapping_add(1);
+        }
+        self.write.store(write, Ordering::Release);
+
+        let min_read = write.wrapping_sub(self.mask + 1);
+        let mut read = self.read.load(Ordering::Acquire);
+        while write.wrapping_sub(read) > self.mask + 1 {
+            match self
+                .read
+                .compare_exchange_weak(read, min_read, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(actual) => read = actual,
+            }
+        }
+    }
+
+    /// Pulls up to `out.len()` samples, returning how many were written.
+    /// Only the consumer may call this.
+    pub(crate) fn pull(&self, out: &mut [f32]) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        let mut read = self.read.load(Ordering::Relaxed);
+
+        let mut written = 0;
+        while written < out.len() && read != write {
+            out[written] = f32::from_bits(self.data[read & self.mask].load(Ordering::Relaxed));
+            read = read.wrapping_add(1);
+            written += 1;
+        }
+        self.read.store(read, Ordering::Release);
+        written
+    }
+}
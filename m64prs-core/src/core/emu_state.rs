@@ -1,6 +1,7 @@
 use std::{
-    ffi::{c_int, c_void},
+    ffi::{c_int, c_void, CString},
     future::Future,
+    path::Path,
     pin::Pin,
     sync::mpsc,
     task::{Context, Poll},
@@ -13,6 +14,15 @@ use crate::error::M64PError;
 
 use super::Core;
 
+/// Where a save/load-state operation reads from or writes to.
+#[derive(Debug, Clone, Copy)]
+pub enum SaveSlot<'a> {
+    /// One of the 10 numbered `.st` slots mupen64plus manages itself.
+    Slot(u8),
+    /// An explicit savestate file.
+    Path(&'a Path),
+}
+
 // Asynchronous core commands
 impl Core {
     /// Stops the currently-running ROM.
@@ -45,12 +55,28 @@ impl Core {
         self.emu_state_command(Command::Nop, state).await.unwrap()
     }
 
+    /// Saves the emulator's state to `target`, returning once the core
+    /// reports that the save has completed.
+    pub async fn save_state(&self, target: SaveSlot<'_>) -> Result<(), M64PError> {
+        let _lock = self.emu_mutex.lock().await;
+        self.state_command(target, Command::StateSave, CoreParam::StateSaveComplete)
+            .await
+    }
+
+    /// Loads the emulator's state from `target`, returning once the core
+    /// reports that the load has completed.
+    pub async fn load_state(&self, target: SaveSlot<'_>) -> Result<(), M64PError> {
+        let _lock = self.emu_mutex.lock().await;
+        self.state_command(target, Command::StateLoad, CoreParam::StateLoadComplete)
+            .await
+    }
+
     fn emu_state_command(
         &self,
         command: Command,
         value: EmuState,
     ) -> impl Future<Output = Result<(), M64PError>> {
-        let (mut future, waiter) = emu_pair(u32::from(value) as i32);
+        let (mut future, waiter) = emu_pair(CoreParam::EmuState, u32::from(value) as i32);
         self.emu_sender
             .send(waiter)
             .expect("emu waiter queue disconnected");
@@ -62,6 +88,40 @@ impl Core {
         future
     }
 
+    /// Issues `command` (a `StateSave`/`StateLoad`) against `target`, waiting
+    /// on `complete_param` (a `StateSaveComplete`/`StateLoadComplete`) for the
+    /// core to report completion.
+    fn state_command(
+        &self,
+        target: SaveSlot<'_>,
+        command: Command,
+        complete_param: CoreParam,
+    ) -> impl Future<Output = Result<(), M64PError>> {
+        // The completion param fires with `1` on success and `0` on failure;
+        // either one resolves the future, the latter as an `Err`.
+        let (mut future, waiter) = emu_pair_fallible(complete_param, 1, 0);
+        self.emu_sender
+            .send(waiter)
+            .expect("emu waiter queue disconnected");
+
+        let result = match target {
+            SaveSlot::Slot(slot) => self
+                .do_command_i(Command::StateSetSlot, slot as c_int)
+                .and_then(|_| self.do_command(command)),
+            SaveSlot::Path(path) => CString::new(path.to_string_lossy().as_bytes())
+                .map_err(|_| M64PError::InputInvalid)
+                .and_then(|path| unsafe {
+                    self.do_command_ip(command, 1, path.as_ptr() as *mut c_void)
+                }),
+        };
+
+        if let Err(error) = result {
+            future.fail_early(error);
+        }
+
+        future
+    }
+
     /// Notifies the graphics plugin of a change in the window's size.
     pub fn notify_resize(&self, width: u16, height: u16) -> Result<(), M64PError> {
         let size_packed = (((width as u32) << 16) | (height as u32)) as c_int;
@@ -76,13 +136,20 @@ impl Core {
 }
 
 pub(crate) struct EmulatorWaiter {
+    param: CoreParam,
     value: c_int,
-    tx: oneshot::Sender<()>,
+    /// A second value that also trips this waiter, e.g. the `0` a
+    /// `*Complete` param fires with on failure alongside the `1` it fires
+    /// with on success. Tripping on this value resolves the future as
+    /// `Err`.
+    fail_value: Option<c_int>,
+    tx: oneshot::Sender<c_int>,
 }
 
 pub(crate) struct EmulatorFuture {
     early_fail: Option<M64PError>,
-    rx: oneshot::Receiver<()>,
+    fail_value: Option<c_int>,
+    rx: oneshot::Receiver<c_int>,
 }
 
 impl Future for EmulatorFuture {
@@ -94,6 +161,9 @@ impl Future for EmulatorFuture {
         }
 
         match Future::poll(Pin::new(&mut self.rx), cx) {
+            Poll::Ready(Ok(value)) if Some(value) == self.fail_value => {
+                Poll::Ready(Err(M64PError::SystemFail))
+            }
             Poll::Ready(_) => Poll::Ready(Ok(())),
             Poll::Pending => Poll::Pending,
         }
@@ -106,14 +176,40 @@ impl EmulatorFuture {
     }
 }
 
-fn emu_pair(value: c_int) -> (EmulatorFuture, EmulatorWaiter) {
+/// Pairs a future/waiter that resolves `Ok` the moment `param` fires with
+/// `value`.
+fn emu_pair(param: CoreParam, value: c_int) -> (EmulatorFuture, EmulatorWaiter) {
+    emu_pair_inner(param, value, None)
+}
+
+/// Like [`emu_pair`], but also resolves the future as `Err` if `param` fires
+/// with `fail_value` instead of `value`.
+fn emu_pair_fallible(
+    param: CoreParam,
+    value: c_int,
+    fail_value: c_int,
+) -> (EmulatorFuture, EmulatorWaiter) {
+    emu_pair_inner(param, value, Some(fail_value))
+}
+
+fn emu_pair_inner(
+    param: CoreParam,
+    value: c_int,
+    fail_value: Option<c_int>,
+) -> (EmulatorFuture, EmulatorWaiter) {
     let (tx, rx) = oneshot::channel();
     (
         EmulatorFuture {
             early_fail: None,
+            fail_value,
             rx,
         },
-        EmulatorWaiter { value, tx },
+        EmulatorWaiter {
+            param,
+            value,
+            fail_value,
+            tx,
+        },
     )
 }
 
@@ -129,7 +225,11 @@ impl EmulatorWaitManager {
         }
     }
 
-    pub fn on_emu_state_changed(&mut self, value: c_int) {
+    /// Notifies the manager that `param` changed to `value`, tripping (and
+    /// removing) any waiter registered for `param` whose success value or
+    /// fail value matches `value`. `EmuState` changes and savestate
+    /// completions both flow through here.
+    pub fn on_core_param_changed(&mut self, param: CoreParam, value: c_int) {
         // add any new waiters that may need to be processed
         while let Ok(next) = self.rx.try_recv() {
             self.waiters.push(next);
@@ -138,9 +238,12 @@ impl EmulatorWaitManager {
         // if any waiters need to be tripped, trip them now and remove them.
         let mut i = 0;
         while i < self.waiters.len() {
-            if self.waiters[i].value == value {
+            let waiter = &self.waiters[i];
+            let matches = waiter.param == param
+                && (waiter.value == value || waiter.fail_value == Some(value));
+            if matches {
                 let waiter = self.waiters.swap_remove(i);
-                let _ = waiter.tx.send(());
+                let _ = waiter.tx.send(value);
             } else {
                 i += 1;
             }
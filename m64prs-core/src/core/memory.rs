@@ -0,0 +1,118 @@
+use std::mem::{size_of, MaybeUninit};
+
+use bytemuck::CheckedBitPattern;
+use m64prs_sys::DebugMemPtrType;
+
+use crate::error::M64PError;
+
+use super::Core;
+
+/// Size of the emulated RDRAM, in bytes. The core always reserves the full
+/// 8 MiB expansion-pak range even on ROMs that only address 4 MiB.
+const RDRAM_SIZE: usize = 0x0080_0000;
+
+/// Base of the cached (KSEG0) virtual alias of RDRAM.
+const KSEG0_BASE: u32 = 0x8000_0000;
+/// Base of the uncached (KSEG1) virtual alias of RDRAM.
+const KSEG1_BASE: u32 = 0xA000_0000;
+
+// Memory-inspection API
+impl Core {
+    /// Reads a `T` out of RDRAM at `addr`, validating its bit pattern.
+    ///
+    /// `addr` may be a KSEG0/KSEG1 virtual address or a bare physical offset;
+    /// both are translated to an offset into RDRAM and bounds-checked.
+    pub fn read<T: CheckedBitPattern>(&self, addr: u32) -> Result<T, M64PError> {
+        let mut bits = MaybeUninit::<T::Bits>::zeroed();
+        // SAFETY: `bits` is a `size_of::<T::Bits>()`-byte allocation that we
+        // fully overwrite before reading it back out below.
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(bits.as_mut_ptr() as *mut u8, size_of::<T::Bits>())
+        };
+        self.read_into(addr, buf)?;
+        // SAFETY: `read_into` filled every byte of `buf`.
+        let bits = unsafe { bits.assume_init() };
+
+        if T::is_valid_bit_pattern(&bits) {
+            // SAFETY: just confirmed `bits` is a valid bit pattern for `T`,
+            // and `T::Bits` is guaranteed to have the same layout as `T`.
+            Ok(unsafe { std::mem::transmute_copy(&bits) })
+        } else {
+            Err(M64PError::InvalidState)
+        }
+    }
+
+    /// Reads `buf.len()` raw bytes out of RDRAM starting at `addr`.
+    pub fn read_into(&self, addr: u32, buf: &mut [u8]) -> Result<(), M64PError> {
+        let offset = Self::translate_rdram_addr(addr, buf.len())?;
+        let base = self.rdram_base()?;
+        // SAFETY: `translate_rdram_addr` proved `[offset, offset + buf.len())`
+        // lies within the `RDRAM_SIZE`-byte region pointed to by `base`.
+        unsafe {
+            std::ptr::copy_nonoverlapping(base.add(offset), buf.as_mut_ptr(), buf.len());
+        }
+        Ok(())
+    }
+
+    /// Writes a `T`'s raw bytes into RDRAM at `addr`.
+    pub fn write<T: CheckedBitPattern>(&self, addr: u32, value: T) -> Result<(), M64PError> {
+        // SAFETY: `T::Bits` has the same layout as `T` by construction of
+        // `CheckedBitPattern`, so reinterpreting the bytes is sound.
+        let bits = unsafe { std::mem::transmute_copy::<T, T::Bits>(&value) };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&bits as *const T::Bits as *const u8, size_of::<T::Bits>())
+        };
+        self.write_from(addr, bytes)
+    }
+
+    /// Writes raw bytes into RDRAM starting at `addr`.
+    pub fn write_from(&self, addr: u32, buf: &[u8]) -> Result<(), M64PError> {
+        let offset = Self::translate_rdram_addr(addr, buf.len())?;
+        let base = self.rdram_base()?;
+        // SAFETY: see `read_into`; the same bounds proof applies symmetrically.
+        unsafe {
+            std::ptr::copy_nonoverlapping(buf.as_ptr(), base.add(offset), buf.len());
+        }
+        Ok(())
+    }
+
+    /// Translates a KSEG0/KSEG1/physical address into an RDRAM byte offset,
+    /// bounds-checking the `len`-byte access against `RDRAM_SIZE`.
+    fn translate_rdram_addr(addr: u32, len: usize) -> Result<usize, M64PError> {
+        let offset = match addr {
+            KSEG0_BASE..=0x9FFF_FFFF => addr - KSEG0_BASE,
+            KSEG1_BASE..=0xBFFF_FFFF => addr - KSEG1_BASE,
+            phys if (phys as usize) < RDRAM_SIZE => phys,
+            _ => return Err(M64PError::InputInvalid),
+        } as usize;
+
+        if offset.checked_add(len).is_some_and(|end| end <= RDRAM_SIZE) {
+            Ok(offset)
+        } else {
+            Err(M64PError::InputInvalid)
+        }
+    }
+
+    /// Resolves and caches the base pointer to RDRAM for this ROM session.
+    ///
+    /// mupen64plus reallocates RDRAM on every `open_rom`, so this cache is
+    /// only valid while the ROM that warmed it stays open. Nothing in this
+    /// file clears it on close, so closing a ROM and opening another
+    /// currently leaves `read`/`write`/`read_into`/`write_from` dereferencing
+    /// a stale pointer into freed memory; invalidating the cache from
+    /// `Core::close_rom` is a prerequisite for this API being safe to use
+    /// across more than one ROM per process.
+    fn rdram_base(&self) -> Result<*mut u8, M64PError> {
+        let cached = self.rdram_base.get_or_try_init(|| {
+            // SAFETY: `DebugMemGetPointer` is valid to call whenever a ROM is
+            // open, which every `Core::read`/`write` caller is required to ensure.
+            let ptr = unsafe { self.do_debug_mem_get_pointer(DebugMemPtrType::Rdram)? };
+            if ptr.is_null() {
+                Err(M64PError::InvalidState)
+            } else {
+                Ok(ptr as usize)
+            }
+        })?;
+        Ok(*cached as *mut u8)
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::mpsc,
+    task::{Context, Poll},
+};
+
+use bytemuck::CheckedBitPattern;
+use futures::channel::oneshot;
+
+use crate::error::M64PError;
+
+use super::Core;
+
+// Async memory-watch API
+impl Core {
+    /// Resolves the first frame at which `predicate` returns `true` for the
+    /// value read from `addr`.
+    pub fn watch<T, F>(&self, addr: u32, predicate: F) -> impl Future<Output = Result<(), M64PError>>
+    where
+        T: CheckedBitPattern,
+        F: FnMut(T) -> bool + Send + 'static,
+    {
+        self.watch_inner(addr, predicate, None)
+    }
+
+    /// Like [`Core::watch`], but fails with [`M64PError::InputInvalid`] if the
+    /// predicate hasn't tripped within `timeout_frames` frames.
+    pub fn watch_timeout<T, F>(
+        &self,
+        addr: u32,
+        predicate: F,
+        timeout_frames: u32,
+    ) -> impl Future<Output = Result<(), M64PError>>
+    where
+        T: CheckedBitPattern,
+        F: FnMut(T) -> bool + Send + 'static,
+    {
+        self.watch_inner(addr, predicate, Some(timeout_frames))
+    }
+
+    /// Resolves the first frame at which the value at `addr` equals `value`.
+    pub fn watch_eq<T>(&self, addr: u32, value: T) -> impl Future<Output = Result<(), M64PError>>
+    where
+        T: CheckedBitPattern + PartialEq + Send + 'static,
+    {
+        self.watch(addr, move |current| current == value)
+    }
+
+    fn watch_inner<T, F>(
+        &self,
+        addr: u32,
+        mut predicate: F,
+        timeout_frames: Option<u32>,
+    ) -> impl Future<Output = Result<(), M64PError>>
+    where
+        T: CheckedBitPattern,
+        F: FnMut(T) -> bool + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let decode = move |core: &Core| core.read::<T>(addr).ok().map(&mut predicate);
+
+        self.watch_sender
+            .send(MemoryWatcher {
+                decode: Box::new(decode),
+                remaining_frames: timeout_frames,
+                tx: Some(tx),
+            })
+            .expect("memory watcher queue disconnected");
+
+        MemoryWatchFuture { rx }
+    }
+}
+
+pub(crate) struct MemoryWatcher {
+    /// Reads and evaluates the watched value; `None` means the read itself
+    /// failed (e.g. no ROM open) and should be retried next frame.
+    decode: Box<dyn FnMut(&Core) -> Option<bool> + Send>,
+    remaining_frames: Option<u32>,
+    tx: Option<oneshot::Sender<Result<(), M64PError>>>,
+}
+
+pub(crate) struct MemoryWatchFuture {
+    rx: oneshot::Receiver<Result<(), M64PError>>,
+}
+
+impl Future for MemoryWatchFuture {
+    type Output = Result<(), M64PError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Future::poll(Pin::new(&mut self.rx), cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(M64PError::InvalidState)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) struct MemoryWatchManager {
+    rx: mpsc::Receiver<MemoryWatcher>,
+    watchers: Vec<MemoryWatcher>,
+}
+
+impl MemoryWatchManager {
+    pub fn new(rx: mpsc::Receiver<MemoryWatcher>) -> Self {
+        Self {
+            rx,
+            watchers: Vec::new(),
+        }
+    }
+
+    /// Called once per VI/frame callback. Evaluates every registered
+    /// watcher's predicate against current memory and trips (removing) the
+    /// ones that matched or timed out, exactly like
+    /// [`EmulatorWaitManager::on_core_param_changed`](super::emu_state::EmulatorWaitManager::on_core_param_changed).
+    pub fn on_vi_frame(&mut self, core: &Core) {
+        while let Ok(next) = self.rx.try_recv() {
+            self.watchers.push(next);
+        }
+
+        let mut i = 0;
+        while i < self.watchers.len() {
+            let result = match (self.watchers[i].decode)(core) {
+                Some(true) => Some(Ok(())),
+                _ => match &mut self.watchers[i].remaining_frames {
+                    Some(remaining) => {
+                        *remaining = remaining.saturating_sub(1);
+                        if *remaining == 0 {
+                            Some(Err(M64PError::InputInvalid))
+                        } else {
+                            None
+                        }
+                    }
+                    None => None,
+                },
+            };
+
+            if let Some(result) = result {
+                let mut watcher = self.watchers.swap_remove(i);
+                let _ = watcher.tx.take().unwrap().send(result);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    env,
+    ffi::{c_char, c_int, CStr},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Plugin, PluginSet};
+
+/// The four plugin categories mupen64plus loads; mirrors `M64PLUGIN_GFX` and
+/// friends from the plugin ABI, minus `M64PLUGIN_CORE` and `M64PLUGIN_NULL`,
+/// which aren't things a frontend picks between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PluginCategory {
+    Rsp,
+    Graphics,
+    Audio,
+    Input,
+}
+
+impl PluginCategory {
+    fn from_abi(raw: c_int) -> Option<Self> {
+        match raw {
+            1 => Some(Self::Rsp),
+            2 => Some(Self::Graphics),
+            3 => Some(Self::Audio),
+            4 => Some(Self::Input),
+            _ => None,
+        }
+    }
+}
+
+/// Opaque handle identifying one discovered plugin. Stable for the lifetime
+/// of the [`PluginCatalog`] it came from, but not across a re-discovery.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PluginId(PathBuf);
+
+/// Metadata about one discovered plugin shared library.
+#[derive(Debug, Clone)]
+pub struct PluginInfo {
+    pub id: PluginId,
+    pub category: PluginCategory,
+    pub name: String,
+    pub version: u32,
+    pub path: PathBuf,
+}
+
+/// Every plugin found across the search path, grouped by category.
+#[derive(Debug, Default, Clone)]
+pub struct PluginCatalog {
+    by_category: HashMap<PluginCategory, Vec<PluginInfo>>,
+}
+
+impl PluginCatalog {
+    /// Searches, in order: the directory containing the current executable,
+    /// every path in the `M64PRS_PLUGIN_PATH` environment variable (using
+    /// the platform's native `PATH`-style separator), and the platform's
+    /// default plugin install directory. Files that aren't a valid,
+    /// ABI-compatible plugin are silently skipped.
+    pub fn discover() -> Self {
+        let mut dirs = Vec::new();
+        if let Ok(exe) = env::current_exe() {
+            if let Some(parent) = exe.parent() {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+        if let Some(path_var) = env::var_os("M64PRS_PLUGIN_PATH") {
+            dirs.extend(env::split_paths(&path_var));
+        }
+        dirs.push(platform_default_dir());
+
+        Self::discover_in(&dirs)
+    }
+
+    /// Like [`PluginCatalog::discover`], but searching exactly `dirs` and
+    /// nothing else. Useful for tests and for frontends with their own
+    /// notion of a plugin directory.
+    pub fn discover_in(dirs: &[PathBuf]) -> Self {
+        let mut by_category: HashMap<PluginCategory, Vec<PluginInfo>> = HashMap::new();
+
+        for dir in dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !is_candidate_library(&path) {
+                    continue;
+                }
+                if let Some(info) = probe_plugin(&path) {
+                    by_category.entry(info.category).or_default().push(info);
+                }
+            }
+        }
+
+        Self { by_category }
+    }
+
+    /// All plugins discovered for `category`.
+    pub fn plugins(&self, category: PluginCategory) -> &[PluginInfo] {
+        self.by_category
+            .get(&category)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Looks up the full metadata for a previously-discovered plugin id.
+    pub fn plugin_info(&self, id: &PluginId) -> Option<&PluginInfo> {
+        self.by_category
+            .values()
+            .flatten()
+            .find(|info| &info.id == id)
+    }
+
+    /// Picks the first available plugin in each category, returning `None`
+    /// if any category has no candidates at all.
+    pub fn default_selection(&self) -> Option<PluginSelection> {
+        Some(PluginSelection {
+            graphics: self.plugins(PluginCategory::Graphics).first()?.id.clone(),
+            audio: self.plugins(PluginCategory::Audio).first()?.id.clone(),
+            input: self.plugins(PluginCategory::Input).first()?.id.clone(),
+            rsp: self.plugins(PluginCategory::Rsp).first()?.id.clone(),
+        })
+    }
+
+    /// Loads the plugins in `selection`, returning a [`PluginSet`] ready to
+    /// attach to a `Core`, or the first category whose id doesn't resolve to
+    /// a plugin in this catalog (e.g. it was removed since discovery ran).
+    pub fn build_plugin_set(
+        &self,
+        selection: &PluginSelection,
+    ) -> Result<PluginSet, PluginCategory> {
+        Ok(PluginSet {
+            graphics: self.load_selected(PluginCategory::Graphics, &selection.graphics)?,
+            audio: self.load_selected(PluginCategory::Audio, &selection.audio)?,
+            input: self.load_selected(PluginCategory::Input, &selection.input)?,
+            rsp: self.load_selected(PluginCategory::Rsp, &selection.rsp)?,
+        })
+    }
+
+    fn load_selected(
+        &self,
+        category: PluginCategory,
+        id: &PluginId,
+    ) -> Result<Plugin, PluginCategory> {
+        let info = self.plugin_info(id).ok_or(category)?;
+        Plugin::load(&info.path).map_err(|_| category)
+    }
+}
+
+/// A user's (or a default) choice of one plugin per category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSelection {
+    pub graphics: PluginId,
+    pub audio: PluginId,
+    pub input: PluginId,
+    pub rsp: PluginId,
+}
+
+impl fmt::Display for PluginCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Rsp => "RSP",
+            Self::Graphics => "graphics",
+            Self::Audio => "audio",
+            Self::Input => "input",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn is_candidate_library(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "dll")
+}
+#[cfg(target_os = "linux")]
+fn is_candidate_library(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "so")
+}
+
+#[cfg(target_os = "windows")]
+fn platform_default_dir() -> PathBuf {
+    env::var_os("ProgramFiles")
+        .map(|dir| PathBuf::from(dir).join("mupen64plus"))
+        .unwrap_or_else(|| PathBuf::from(r"C:\Program Files\mupen64plus"))
+}
+#[cfg(target_os = "linux")]
+fn platform_default_dir() -> PathBuf {
+    PathBuf::from("/usr/lib/mupen64plus")
+}
+
+/// Probes `path` for the mupen64plus plugin ABI's `PluginGetVersion` export,
+/// returning its reported category, name, and version if it's there.
+fn probe_plugin(path: &Path) -> Option<PluginInfo> {
+    type GetVersionFn = unsafe extern "C" fn(
+        *mut c_int,
+        *mut c_int,
+        *mut c_int,
+        *mut *const c_char,
+        *mut c_int,
+    ) -> c_int;
+
+    // SAFETY: `get_version` is called with pointers to local, fully-sized
+    // out-params exactly as the plugin ABI requires, and the returned name
+    // pointer is read (and copied) before `library` is dropped.
+    let (category, name, version) = unsafe {
+        let library = libloading::Library::new(path).ok()?;
+        let get_version: libloading::Symbol<GetVersionFn> =
+            library.get(b"PluginGetVersion\0").ok()?;
+
+        let mut raw_type: c_int = 0;
+        let mut raw_version: c_int = 0;
+        let mut raw_api_version: c_int = 0;
+        let mut raw_name: *const c_char = std::ptr::null();
+        let mut raw_caps: c_int = 0;
+
+        if get_version(
+            &mut raw_type,
+            &mut raw_version,
+            &mut raw_api_version,
+            &mut raw_name,
+            &mut raw_caps,
+        ) != 0
+        {
+            return None;
+        }
+
+        let name = if raw_name.is_null() {
+            path.file_stem()?.to_string_lossy().into_owned()
+        } else {
+            CStr::from_ptr(raw_name).to_string_lossy().into_owned()
+        };
+
+        (PluginCategory::from_abi(raw_type)?, name, raw_version as u32)
+    };
+
+    Some(PluginInfo {
+        id: PluginId(path.to_path_buf()),
+        category,
+        name,
+        version,
+        path: path.to_path_buf(),
+    })
+}
+
@@ -0,0 +1,437 @@
+use std::{
+    env, io,
+    io::{BufReader, BufWriter, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command as ProcCommand, Stdio},
+    sync::{
+        atomic::{self, AtomicU64},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+};
+
+use futures::{future, FutureExt};
+use m64prs_core::plugin::discovery::{PluginCatalog, PluginSelection};
+use m64prs_sys::EmuState;
+use relm4::ComponentSender;
+use serde::{Deserialize, Serialize};
+
+use super::{Model, Update};
+
+/// A command sent to the out-of-process core host. Mirrors [`super::Request`]
+/// one-for-one, minus the parts (e.g. a `PathBuf`) that don't survive a
+/// cross-process hop unchanged.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum HostCommand {
+    Init,
+    StartRom(Vec<u8>, PluginSelection),
+    StopRom,
+    Pause,
+    Resume,
+    AdvanceFrame,
+}
+
+/// An event emitted by the core host, either as a reply to a `HostCommand`
+/// or (for `EmuStateChange`) unprompted.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum HostEvent {
+    CoreReady,
+    /// Acknowledges a command (e.g. `StopRom`, `Pause`) that doesn't carry
+    /// any data of its own on success.
+    Ack,
+    Error(String),
+    EmuStateChange(EmuState),
+}
+
+/// Hosts `m64prs_core::Core` in a child process and drives it over
+/// length-prefixed, bincode-encoded frames on its stdin/stdout, so that a
+/// segfault in the core or a native plugin takes down the child instead of
+/// this process. This mirrors `vidext::request::RequestManager`'s
+/// request-id/matched-reply shape; the difference is that replies are
+/// demultiplexed from unprompted `EmuStateChange` events by a background
+/// reader thread rather than read inline.
+pub(super) struct CoreHost {
+    next_id: AtomicU64,
+    child: Child,
+    stdin: Mutex<BufWriter<ChildStdin>>,
+    inbound: mpsc::Receiver<(u64, HostEvent)>,
+}
+
+impl std::fmt::Debug for CoreHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoreHost")
+            .field("child", &self.child.id())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CoreHost {
+    /// Relaunches the current executable in `--core-host` mode and wires up
+    /// its stdio as the IPC transport. `main` must check for that flag
+    /// before building the GUI and dispatch straight to
+    /// [`super::run_core_host`] instead; otherwise this just spawns a second
+    /// copy of the whole application.
+    pub(super) fn spawn(sender: &ComponentSender<Model>) -> io::Result<Self> {
+        let mut child = ProcCommand::new(env::current_exe()?)
+            .arg("--core-host")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("child stdin should be piped");
+        let stdout = child.stdout.take().expect("child stdout should be piped");
+
+        let (tx, rx) = mpsc::channel();
+        let sender = sender.clone();
+        thread::spawn(move || Self::read_loop(BufReader::new(stdout), tx, sender));
+
+        Ok(Self {
+            next_id: AtomicU64::new(0),
+            child,
+            stdin: Mutex::new(BufWriter::new(stdin)),
+            inbound: rx,
+        })
+    }
+
+    /// Sends `command` to the host and blocks until its matching reply
+    /// arrives, exactly like `RequestManager::request`.
+    pub(super) fn request(&self, command: HostCommand) -> io::Result<HostEvent> {
+        let id = self.next_id.fetch_add(1, atomic::Ordering::AcqRel);
+        {
+            let mut stdin = self.stdin.lock().expect("stdin lock shouldn't be poisoned");
+            write_frame(&mut *stdin, &(id, command))?;
+        }
+
+        loop {
+            let (reply_id, event) = self
+                .inbound
+                .recv()
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "core host exited"))?;
+            if reply_id == id {
+                return Ok(event);
+            }
+            // a reply to some other request that nothing is waiting on; drop it.
+        }
+    }
+
+    /// Background loop forwarding unprompted events straight to the UI and
+    /// everything else back to whichever `request()` call is waiting on it.
+    fn read_loop(
+        mut stdout: BufReader<ChildStdout>,
+        inbound: mpsc::Sender<(u64, HostEvent)>,
+        sender: ComponentSender<Model>,
+    ) {
+        while let Ok((id, event)) = read_frame::<(u64, HostEvent)>(&mut stdout) {
+            if let HostEvent::EmuStateChange(state) = &event {
+                let _ = sender.output(Update::EmuStateChange(*state));
+                continue;
+            }
+            if inbound.send((id, event)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for CoreHost {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        // `kill` only sends the signal; reap the zombie ourselves since
+        // nothing else is waiting on this child.
+        let _ = self.child.wait();
+    }
+}
+
+/// The child process's side of the session, mirroring `super::ModelInner`
+/// minus the states (`Uninit` before `Init`'s reply, anything relm4-specific)
+/// that don't exist once we're past that point.
+enum HostSession {
+    Ready {
+        core: m64prs_core::Core,
+        catalog: PluginCatalog,
+    },
+    Running {
+        join_handle: thread::JoinHandle<()>,
+        /// Forwards unprompted `EmuStateChange` events to the parent; see
+        /// `start_rom`. Joined in `stop_rom` so its last `Arc<Core>` clone is
+        /// gone before `Arc::into_inner`.
+        notify_handle: thread::JoinHandle<()>,
+        core_ref: Arc<m64prs_core::Core>,
+        catalog: PluginCatalog,
+    },
+}
+
+/// The shared stdout transport, so both the command loop and a session's
+/// background notify thread can write frames without interleaving them.
+type SharedWriter = Arc<Mutex<BufWriter<io::Stdout>>>;
+
+/// Entry point for a `--core-host` child process (see
+/// [`CoreHost::spawn`]'s doc comment for how `main` should reach this).
+/// Reads `HostCommand` frames from stdin, drives a single
+/// `m64prs_core::Core` against them, and writes `HostEvent` replies to
+/// stdout until the pipe closes (the parent exited or dropped `CoreHost`).
+pub(super) fn run_host() -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    const MUPEN_FILENAME: &str = "mupen64plus.dll";
+    #[cfg(target_os = "linux")]
+    const MUPEN_FILENAME: &str = "libmupen64plus.so";
+
+    let mut reader = BufReader::new(io::stdin());
+    let writer: SharedWriter = Arc::new(Mutex::new(BufWriter::new(io::stdout())));
+
+    // `Init` is handled outside `HostSession`: there's no core to hold yet.
+    let (id, command) = read_frame::<(u64, HostCommand)>(&mut reader)?;
+    if !matches!(command, HostCommand::Init) {
+        panic!("core host's first command should be Init");
+    }
+    let mupen_dll_path = env::current_exe()?
+        .parent()
+        .expect("should be able to access other file in the same folder")
+        .join(MUPEN_FILENAME);
+    let mut session = match m64prs_core::Core::init(mupen_dll_path) {
+        Ok(core) => {
+            write_frame(&mut *writer.lock().unwrap(), &(id, HostEvent::CoreReady))?;
+            HostSession::Ready {
+                core,
+                catalog: PluginCatalog::discover(),
+            }
+        }
+        Err(error) => {
+            write_frame(
+                &mut *writer.lock().unwrap(),
+                &(id, HostEvent::Error(error.to_string())),
+            )?;
+            return Ok(());
+        }
+    };
+
+    while let Ok((id, command)) = read_frame::<(u64, HostCommand)>(&mut reader) {
+        let (event, next) = session.handle(command, &writer);
+        session = next;
+        write_frame(&mut *writer.lock().unwrap(), &(id, event))?;
+    }
+    Ok(())
+}
+
+impl HostSession {
+    /// Applies `command`, returning the reply to send and the session's next
+    /// state (unchanged if `command` didn't apply or only acted on the
+    /// shared `core_ref`). `writer` is handed to `start_rom` so its
+    /// `EmuStateChange` forwarder thread can reach the same transport as the
+    /// reply this call is about to write.
+    fn handle(self, command: HostCommand, writer: &SharedWriter) -> (HostEvent, HostSession) {
+        match (self, command) {
+            (HostSession::Ready { core, catalog }, HostCommand::StartRom(rom_data, selection)) => {
+                Self::start_rom(core, catalog, rom_data, selection, writer)
+            }
+            (
+                HostSession::Running {
+                    join_handle,
+                    notify_handle,
+                    core_ref,
+                    catalog,
+                },
+                HostCommand::StopRom,
+            ) => Self::stop_rom(join_handle, notify_handle, core_ref, catalog),
+            (
+                HostSession::Running {
+                    join_handle,
+                    notify_handle,
+                    core_ref,
+                    catalog,
+                },
+                HostCommand::Pause,
+            ) => {
+                let event = Self::state_reply(pollster::block_on(core_ref.pause()));
+                (
+                    event,
+                    HostSession::Running {
+                        join_handle,
+                        notify_handle,
+                        core_ref,
+                        catalog,
+                    },
+                )
+            }
+            (
+                HostSession::Running {
+                    join_handle,
+                    notify_handle,
+                    core_ref,
+                    catalog,
+                },
+                HostCommand::Resume,
+            ) => {
+                let event = Self::state_reply(pollster::block_on(core_ref.resume()));
+                (
+                    event,
+                    HostSession::Running {
+                        join_handle,
+                        notify_handle,
+                        core_ref,
+                        catalog,
+                    },
+                )
+            }
+            (
+                HostSession::Running {
+                    join_handle,
+                    notify_handle,
+                    core_ref,
+                    catalog,
+                },
+                HostCommand::AdvanceFrame,
+            ) => {
+                let event = Self::state_reply(pollster::block_on(core_ref.advance_frame()));
+                (
+                    event,
+                    HostSession::Running {
+                        join_handle,
+                        notify_handle,
+                        core_ref,
+                        catalog,
+                    },
+                )
+            }
+            (session, _) => (
+                HostEvent::Error("command not valid in the current state".into()),
+                session,
+            ),
+        }
+    }
+
+    fn start_rom(
+        mut core: m64prs_core::Core,
+        catalog: PluginCatalog,
+        rom_data: Vec<u8>,
+        selection: PluginSelection,
+        writer: &SharedWriter,
+    ) -> (HostEvent, HostSession) {
+        let plugins = match catalog.build_plugin_set(&selection) {
+            Ok(plugins) => plugins,
+            Err(category) => {
+                return (
+                    HostEvent::Error(format!("no {category} plugin available")),
+                    HostSession::Ready { core, catalog },
+                )
+            }
+        };
+
+        if let Err(error) = core.open_rom(&rom_data) {
+            return (
+                HostEvent::Error(error.to_string()),
+                HostSession::Ready { core, catalog },
+            );
+        }
+        if let Err(error) = core.attach_plugins(plugins) {
+            core.close_rom().expect("there should be an open ROM");
+            return (
+                HostEvent::Error(error.to_string()),
+                HostSession::Ready { core, catalog },
+            );
+        }
+
+        let core_ref = Arc::new(core);
+        let join_handle = {
+            let core_ref = Arc::clone(&core_ref);
+            thread::spawn(move || {
+                let _ = core_ref.execute();
+            })
+        };
+        pollster::block_on(core_ref.await_emu_state(EmuState::Running));
+
+        let notify_handle = {
+            let core_ref = Arc::clone(&core_ref);
+            let writer = Arc::clone(writer);
+            thread::spawn(move || Self::notify_loop(core_ref, writer))
+        };
+
+        (
+            HostEvent::CoreReady,
+            HostSession::Running {
+                join_handle,
+                notify_handle,
+                core_ref,
+                catalog,
+            },
+        )
+    }
+
+    /// Forwards every `EmuState` transition the hosted core makes as an
+    /// unprompted `HostEvent::EmuStateChange`, until it observes `Stopped`
+    /// (at which point `stop_rom` is about to reclaim the only other
+    /// `Arc<Core>` clone, so this thread must let go of its own).
+    fn notify_loop(core_ref: Arc<m64prs_core::Core>, writer: SharedWriter) {
+        loop {
+            let (state, ..) = pollster::block_on(future::select_all([
+                core_ref
+                    .await_emu_state(EmuState::Stopped)
+                    .map(|_| EmuState::Stopped)
+                    .boxed(),
+                core_ref
+                    .await_emu_state(EmuState::Running)
+                    .map(|_| EmuState::Running)
+                    .boxed(),
+                core_ref
+                    .await_emu_state(EmuState::Paused)
+                    .map(|_| EmuState::Paused)
+                    .boxed(),
+            ]));
+
+            let mut writer = writer.lock().expect("stdout lock shouldn't be poisoned");
+            // The id is a don't-care: `read_loop` demuxes by event variant,
+            // not id, and forwards every `EmuStateChange` straight to the UI
+            // regardless of which request (if any) is currently in flight.
+            if write_frame(&mut *writer, &(0u64, HostEvent::EmuStateChange(state))).is_err() {
+                return;
+            }
+            drop(writer);
+
+            if state == EmuState::Stopped {
+                return;
+            }
+        }
+    }
+
+    fn stop_rom(
+        join_handle: thread::JoinHandle<()>,
+        notify_handle: thread::JoinHandle<()>,
+        core_ref: Arc<m64prs_core::Core>,
+        catalog: PluginCatalog,
+    ) -> (HostEvent, HostSession) {
+        pollster::block_on(core_ref.stop()).expect("the core should be running");
+        join_handle.join().expect("the core thread shouldn't panic");
+        notify_handle
+            .join()
+            .expect("the notify thread shouldn't panic");
+
+        let mut core = Arc::into_inner(core_ref)
+            .expect("no refs to the core should exist outside of the emulator thread");
+        core.detach_plugins();
+        core.close_rom().expect("there should be an open ROM");
+
+        (HostEvent::Ack, HostSession::Ready { core, catalog })
+    }
+
+    fn state_reply(result: Result<(), m64prs_core::error::M64PError>) -> HostEvent {
+        match result {
+            Ok(()) => HostEvent::Ack,
+            Err(error) => HostEvent::Error(error.to_string()),
+        }
+    }
+}
+
+fn write_frame<T: Serialize>(writer: &mut impl Write, value: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(value).expect("frame should be serializable");
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+fn read_frame<T: for<'de> Deserialize<'de>>(reader: &mut impl Read) -> io::Result<T> {
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let mut buf = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+    reader.read_exact(&mut buf)?;
+    bincode::deserialize(&buf)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "malformed core-host frame"))
+}
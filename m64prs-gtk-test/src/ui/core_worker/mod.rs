@@ -1,21 +1,32 @@
+mod ipc;
+
 use std::{
     env,
     error::Error,
-    fs, mem,
+    fs, io, mem,
     path::{Path, PathBuf},
     sync::Arc,
     thread::{self, JoinHandle},
 };
 
-use m64prs_core::{plugin::PluginSet, Plugin};
+use m64prs_audio_ringbuffer::AudioSink;
+use m64prs_core::plugin::discovery::{PluginCatalog, PluginCategory, PluginSelection};
 use m64prs_sys::EmuState;
 use relm4::{ComponentSender, Worker};
 
+use ipc::{CoreHost, HostCommand, HostEvent};
+
 #[derive(Debug)]
 pub enum Request {
     Init,
-    StartRom(PathBuf),
+    StartRom {
+        path: PathBuf,
+        plugins: PluginSelection,
+    },
     StopRom,
+    Pause,
+    Resume,
+    AdvanceFrame,
 }
 
 #[derive(Debug)]
@@ -23,6 +34,27 @@ pub enum Update {
     CoreReady,
     Error(Box<dyn Error + Send + 'static>),
     EmuStateChange(EmuState),
+    /// Emitted once a ROM starts in-process, handing over the sink frontends
+    /// can pull captured PCM from. Only the ring-buffer audio plugin can be
+    /// opened as a sink; with any other audio plugin an `Error` is emitted
+    /// instead. Never emitted in [`HostMode::OutOfProcess`].
+    AudioReady(AudioSink),
+    /// Emitted once during startup with every plugin found on the search
+    /// path, so the UI can offer a choice instead of a hardcoded one.
+    PluginsDiscovered(PluginCatalog),
+}
+
+/// Whether the core runs on a thread in this process or in an isolated
+/// child process. See [`ipc::CoreHost`] for the out-of-process transport.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum HostMode {
+    /// Run `m64prs_core::Core` on a thread in this process (the original
+    /// behavior). A misbehaving plugin or core bug can crash the whole UI.
+    #[default]
+    InProcess,
+    /// Run the core in a child process driven over a request/response
+    /// channel, so a crash there surfaces as `Update::Error` instead.
+    OutOfProcess,
 }
 
 /// Inner enum representing the model's current state.
@@ -37,10 +69,18 @@ enum ModelInner {
         join_handle: JoinHandle<()>,
         core_ref: Arc<m64prs_core::Core>,
     },
+    /// The core is hosted in a child process and has no ROM open.
+    HostedReady(CoreHost),
+    /// The core is hosted in a child process and is running a ROM.
+    HostedRunning(CoreHost),
 }
 
 #[derive(Debug)]
-pub struct Model(ModelInner);
+pub struct Model {
+    inner: ModelInner,
+    mode: HostMode,
+    catalog: PluginCatalog,
+}
 
 impl Model {
     fn init(&mut self, sender: &ComponentSender<Self>) {
@@ -49,8 +89,8 @@ impl Model {
         #[cfg(target_os = "linux")]
         const MUPEN_FILENAME: &str = "libmupen64plus.so";
 
-        self.0 = match self.0 {
-            ModelInner::Uninit => {
+        self.inner = match (&self.inner, self.mode) {
+            (ModelInner::Uninit, HostMode::InProcess) => {
                 let self_path = env::current_exe().expect("should be able to find current_exe");
                 let mupen_dll_path = self_path
                     .parent()
@@ -62,13 +102,40 @@ impl Model {
 
                 ModelInner::Ready(core)
             }
+            (ModelInner::Uninit, HostMode::OutOfProcess) => {
+                let host = CoreHost::spawn(sender).expect("should be able to spawn core host");
+                match host.request(HostCommand::Init) {
+                    Ok(HostEvent::CoreReady) => ModelInner::HostedReady(host),
+                    Ok(HostEvent::Error(message)) => {
+                        let _ = sender.output(Update::Error(host_error(message)));
+                        return;
+                    }
+                    Ok(HostEvent::Ack | HostEvent::EmuStateChange(_)) => {
+                        panic!("core host should reply to Init with CoreReady or Error")
+                    }
+                    Err(error) => {
+                        let _ = sender.output(Update::Error(Box::new(error)));
+                        return;
+                    }
+                }
+            }
             _ => panic!("core is already initialized"),
         };
+        self.catalog = PluginCatalog::discover();
+        sender
+            .output(Update::PluginsDiscovered(self.catalog.clone()))
+            .unwrap();
         sender.output(Update::CoreReady).unwrap();
     }
 
-    fn start_rom(&mut self, path: &Path, sender: &ComponentSender<Self>) {
-        self.0 = match mem::replace(&mut self.0, ModelInner::Uninit) {
+    fn start_rom(
+        &mut self,
+        path: &Path,
+        plugins: &PluginSelection,
+        sender: &ComponentSender<Self>,
+    ) {
+        let catalog = &self.catalog;
+        self.inner = match mem::replace(&mut self.inner, ModelInner::Uninit) {
             ModelInner::Uninit => panic!("core should be initialized"),
             ModelInner::Ready(core) => 'core_ready: {
                 let rom_data = match fs::read(path) {
@@ -78,7 +145,7 @@ impl Model {
                         break 'core_ready ModelInner::Ready(core);
                     }
                 };
-                Self::start_rom_inner(&rom_data, core, sender)
+                Self::start_rom_inner(&rom_data, core, catalog, plugins, sender)
             }
             ModelInner::Running {
                 join_handle,
@@ -95,14 +162,38 @@ impl Model {
                     }
                 };
                 let core = Self::stop_rom_inner(join_handle, core_ref, sender);
-                Self::start_rom_inner(&rom_data, core, sender)
+                Self::start_rom_inner(&rom_data, core, catalog, plugins, sender)
+            }
+            ModelInner::HostedReady(host) => 'hosted_ready: {
+                let rom_data = match fs::read(path) {
+                    Ok(data) => data,
+                    Err(error) => {
+                        let _ = sender.output(Update::Error(Box::new(error)));
+                        break 'hosted_ready ModelInner::HostedReady(host);
+                    }
+                };
+                Self::start_hosted_rom_inner(rom_data, plugins.clone(), host, sender)
+            }
+            ModelInner::HostedRunning(host) => {
+                let rom_data = match fs::read(path) {
+                    Ok(data) => data,
+                    Err(error) => {
+                        let _ = sender.output(Update::Error(Box::new(error)));
+                        return;
+                    }
+                };
+                match Self::stop_hosted_rom_inner(host, sender) {
+                    Some(host) => {
+                        Self::start_hosted_rom_inner(rom_data, plugins.clone(), host, sender)
+                    }
+                    None => ModelInner::Uninit,
+                }
             }
         };
-
     }
 
     fn stop_rom(&mut self, sender: &ComponentSender<Self>) {
-        self.0 = match mem::replace(&mut self.0, ModelInner::Uninit) {
+        self.inner = match mem::replace(&mut self.inner, ModelInner::Uninit) {
             ModelInner::Running {
                 join_handle,
                 core_ref,
@@ -114,6 +205,71 @@ impl Model {
 
                 ModelInner::Ready(core)
             }
+            ModelInner::HostedRunning(host) => match Self::stop_hosted_rom_inner(host, sender) {
+                Some(host) => ModelInner::HostedReady(host),
+                None => ModelInner::Uninit,
+            },
+            _ => panic!("core should be running"),
+        };
+    }
+
+    /// Pauses the running ROM in place, without closing it (unlike
+    /// [`Self::stop_rom`]).
+    fn pause(&mut self, sender: &ComponentSender<Self>) {
+        self.inner = match mem::replace(&mut self.inner, ModelInner::Uninit) {
+            ModelInner::Running {
+                join_handle,
+                core_ref,
+            } => {
+                Self::emu_state_command(core_ref.pause(), EmuState::Paused, sender);
+                ModelInner::Running {
+                    join_handle,
+                    core_ref,
+                }
+            }
+            ModelInner::HostedRunning(host) => {
+                Self::hosted_emu_command(host, HostCommand::Pause, EmuState::Paused, sender)
+            }
+            _ => panic!("core should be running"),
+        };
+    }
+
+    /// Resumes a previously-paused ROM.
+    fn resume(&mut self, sender: &ComponentSender<Self>) {
+        self.inner = match mem::replace(&mut self.inner, ModelInner::Uninit) {
+            ModelInner::Running {
+                join_handle,
+                core_ref,
+            } => {
+                Self::emu_state_command(core_ref.resume(), EmuState::Running, sender);
+                ModelInner::Running {
+                    join_handle,
+                    core_ref,
+                }
+            }
+            ModelInner::HostedRunning(host) => {
+                Self::hosted_emu_command(host, HostCommand::Resume, EmuState::Running, sender)
+            }
+            _ => panic!("core should be running"),
+        };
+    }
+
+    /// Advances a paused ROM by a single frame.
+    fn advance_frame(&mut self, sender: &ComponentSender<Self>) {
+        self.inner = match mem::replace(&mut self.inner, ModelInner::Uninit) {
+            ModelInner::Running {
+                join_handle,
+                core_ref,
+            } => {
+                Self::emu_state_command(core_ref.advance_frame(), EmuState::Paused, sender);
+                ModelInner::Running {
+                    join_handle,
+                    core_ref,
+                }
+            }
+            ModelInner::HostedRunning(host) => {
+                Self::hosted_emu_command(host, HostCommand::AdvanceFrame, EmuState::Paused, sender)
+            }
             _ => panic!("core should be running"),
         };
     }
@@ -124,6 +280,8 @@ impl Model {
     fn start_rom_inner(
         rom_data: &[u8],
         mut core: m64prs_core::Core,
+        catalog: &PluginCatalog,
+        selection: &PluginSelection,
         sender: &ComponentSender<Self>,
     ) -> ModelInner {
         macro_rules! check {
@@ -138,17 +296,25 @@ impl Model {
             };
         }
 
-        let plugins = PluginSet {
-            graphics: check!(Plugin::load(
-                "/usr/lib/mupen64plus/mupen64plus-video-rice.so"
-            )),
-            audio: check!(Plugin::load(
-                "/usr/lib/mupen64plus/mupen64plus-audio-sdl.so"
-            )),
-            input: check!(Plugin::load(
-                "/usr/lib/mupen64plus/mupen64plus-input-sdl.so"
-            )),
-            rsp: check!(Plugin::load("/usr/lib/mupen64plus/mupen64plus-rsp-hle.so")),
+        let audio_plugin_path = match catalog.plugin_info(&selection.audio) {
+            Some(info) => info.path.clone(),
+            None => {
+                let _ = sender.output(Update::Error(host_error(format!(
+                    "no {} plugin available",
+                    PluginCategory::Audio
+                ))));
+                return ModelInner::Ready(core);
+            }
+        };
+
+        let plugins = match catalog.build_plugin_set(selection) {
+            Ok(plugins) => plugins,
+            Err(category) => {
+                let _ = sender.output(Update::Error(host_error(format!(
+                    "no {category} plugin available"
+                ))));
+                return ModelInner::Ready(core);
+            }
         };
 
         check!(core.open_rom(&rom_data));
@@ -160,6 +326,15 @@ impl Model {
             return ModelInner::Ready(core);
         }
 
+        match AudioSink::open(&audio_plugin_path) {
+            Ok(sink) => {
+                let _ = sender.output(Update::AudioReady(sink));
+            }
+            Err(err) => {
+                let _ = sender.output(Update::Error(Box::new(err)));
+            }
+        }
+
         let core_ref = Arc::new(core);
 
         let join_handle = {
@@ -189,16 +364,115 @@ impl Model {
         Arc::into_inner(core_ref)
             .expect("no refs to the core should exist outside of the emulator thread")
     }
+
+    fn start_hosted_rom_inner(
+        rom_data: Vec<u8>,
+        plugins: PluginSelection,
+        host: CoreHost,
+        sender: &ComponentSender<Self>,
+    ) -> ModelInner {
+        match host.request(HostCommand::StartRom(rom_data, plugins)) {
+            Ok(HostEvent::CoreReady) => ModelInner::HostedRunning(host),
+            Ok(HostEvent::Error(message)) => {
+                let _ = sender.output(Update::Error(host_error(message)));
+                ModelInner::HostedReady(host)
+            }
+            Ok(HostEvent::Ack | HostEvent::EmuStateChange(_)) => {
+                panic!("core host should reply to StartRom with CoreReady or Error")
+            }
+            Err(error) => {
+                // The pipe is broken or the child is gone; there's no host
+                // left to hold on to, so fall back to `Uninit` and let a
+                // fresh `Request::Init` spin up a new one.
+                let _ = sender.output(Update::Error(Box::new(error)));
+                ModelInner::Uninit
+            }
+        }
+    }
+
+    /// Issues `HostCommand::StopRom` against `host`, returning it back for
+    /// reuse on success. Returns `None` on an I/O error, since that means the
+    /// child is gone and `host` can't be trusted for anything further.
+    fn stop_hosted_rom_inner(host: CoreHost, sender: &ComponentSender<Self>) -> Option<CoreHost> {
+        match host.request(HostCommand::StopRom) {
+            Ok(HostEvent::Ack) => Some(host),
+            Ok(HostEvent::Error(message)) => {
+                let _ = sender.output(Update::Error(host_error(message)));
+                Some(host)
+            }
+            Ok(HostEvent::CoreReady | HostEvent::EmuStateChange(_)) => {
+                panic!("core host should reply to StopRom with Ack or Error")
+            }
+            Err(error) => {
+                let _ = sender.output(Update::Error(Box::new(error)));
+                None
+            }
+        }
+    }
+
+    /// Runs `future` (one of `Core`'s async state-transition methods) to
+    /// completion and reports the result as either an `EmuStateChange` to
+    /// `target` or an `Error`. On success this matches what
+    /// [`Self::start_rom_inner`] emits for the initial transition to
+    /// `Running`, which has no error path of its own.
+    fn emu_state_command(
+        future: impl std::future::Future<Output = Result<(), m64prs_core::error::M64PError>>,
+        target: EmuState,
+        sender: &ComponentSender<Self>,
+    ) {
+        match pollster::block_on(future) {
+            Ok(()) => {
+                let _ = sender.output(Update::EmuStateChange(target));
+            }
+            Err(error) => {
+                let _ = sender.output(Update::Error(Box::new(error)));
+            }
+        }
+    }
+
+    /// Like [`Self::emu_state_command`], but for the hosted case: issues
+    /// `command` and translates the host's `Ack`/`Error` reply the same way.
+    /// An I/O error means `host`'s child is gone, so this falls back to
+    /// `Uninit` instead of holding on to a dead `CoreHost`, exactly like
+    /// [`Self::start_hosted_rom_inner`].
+    fn hosted_emu_command(
+        host: CoreHost,
+        command: HostCommand,
+        target: EmuState,
+        sender: &ComponentSender<Self>,
+    ) -> ModelInner {
+        match host.request(command) {
+            Ok(HostEvent::Ack) => {
+                let _ = sender.output(Update::EmuStateChange(target));
+                ModelInner::HostedRunning(host)
+            }
+            Ok(HostEvent::Error(message)) => {
+                let _ = sender.output(Update::Error(host_error(message)));
+                ModelInner::HostedRunning(host)
+            }
+            Ok(HostEvent::CoreReady | HostEvent::EmuStateChange(_)) => {
+                panic!("core host should reply to a state command with Ack or Error")
+            }
+            Err(error) => {
+                let _ = sender.output(Update::Error(Box::new(error)));
+                ModelInner::Uninit
+            }
+        }
+    }
 }
 
 impl Worker for Model {
-    type Init = ();
+    type Init = HostMode;
 
     type Input = Request;
     type Output = Update;
 
-    fn init(_: Self::Init, sender: ComponentSender<Self>) -> Self {
-        let result = Self(ModelInner::Uninit);
+    fn init(mode: Self::Init, sender: ComponentSender<Self>) -> Self {
+        let result = Self {
+            inner: ModelInner::Uninit,
+            mode,
+            catalog: PluginCatalog::default(),
+        };
         sender.input(Request::Init);
         result
     }
@@ -206,8 +480,24 @@ impl Worker for Model {
     fn update(&mut self, request: Self::Input, sender: ComponentSender<Self>) {
         match request {
             Request::Init => self.init(&sender),
-            Request::StartRom(path) => self.start_rom(&path, &sender),
+            Request::StartRom { path, plugins } => self.start_rom(&path, &plugins, &sender),
             Request::StopRom => self.stop_rom(&sender),
+            Request::Pause => self.pause(&sender),
+            Request::Resume => self.resume(&sender),
+            Request::AdvanceFrame => self.advance_frame(&sender),
         }
     }
 }
+
+/// Wraps a `HostEvent::Error`'s message in an `io::Error` so it can flow
+/// through the same `Box<dyn Error + Send>` as every other `Update::Error`.
+fn host_error(message: String) -> Box<dyn Error + Send> {
+    Box::new(io::Error::new(io::ErrorKind::Other, message))
+}
+
+/// Entry point for a `--core-host` child process. `main` should check for
+/// that flag before building the GUI and call this directly instead, per
+/// [`ipc::CoreHost::spawn`]'s doc comment.
+pub fn run_core_host() -> io::Result<()> {
+    ipc::run_host()
+}